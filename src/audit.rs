@@ -0,0 +1,142 @@
+//! Post-scan audit report.
+//!
+//! After a scan completes, `main.rs` hands every saved file's provenance
+//! (source offset, length, output name, carver extension, optional content
+//! hash) to an [`AuditReport`], which renders it as a human-readable
+//! `audit.txt` and, optionally, a machine-readable `audit.json`. This gives
+//! the tool the chain-of-custody output evidence workflows expect and lets
+//! users dedupe identical carves across runs by hash.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use digest::Digest;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Which digest, if any, to compute over a carved file's bytes.
+///
+/// Hashing is opt-in since it costs an extra pass over every recovered
+/// file; `None` is the default so a plain scan pays nothing for it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum HashAlgorithm {
+    #[default]
+    None,
+    Md5,
+    Sha1,
+}
+
+impl HashAlgorithm {
+    /// Hashes `data`, returning a lowercase hex digest, or `None` when this
+    /// is [`HashAlgorithm::None`].
+    pub fn digest(self, data: &[u8]) -> Option<String> {
+        match self {
+            HashAlgorithm::None => None,
+            HashAlgorithm::Md5 => Some(format!("{:x}", md5::Md5::digest(data))),
+            HashAlgorithm::Sha1 => Some(format!("{:x}", sha1::Sha1::digest(data))),
+        }
+    }
+}
+
+/// Saves carved data to `path`, first hashing it with `algorithm`.
+///
+/// A thin wrapper around [`crate::save_file`] so callers that don't care
+/// about hashing (or chain-of-custody in general) aren't forced to deal
+/// with it.
+pub fn save_file_with_hash(path: &Path, data: &[u8], algorithm: HashAlgorithm) -> Result<Option<String>> {
+    let hash = algorithm.digest(data);
+    crate::save_file(path, data)?;
+    Ok(hash)
+}
+
+/// One recovered file's audit record.
+#[derive(Debug, Serialize)]
+pub struct AuditEntry {
+    pub offset: u64,
+    pub size: usize,
+    pub filename: String,
+    pub extension: String,
+    pub hash: Option<String>,
+}
+
+/// Run-level carve statistics. `failed` is counted as files are saved;
+/// `bytes_scanned` and `elapsed_secs` are set by the caller after the scan.
+#[derive(Debug, Default, Serialize)]
+pub struct AuditStats {
+    pub bytes_scanned: u64,
+    pub elapsed_secs: f64,
+    pub failed: usize,
+}
+
+/// Accumulates [`AuditEntry`] records and [`AuditStats`] for a single scan.
+#[derive(Debug, Default, Serialize)]
+pub struct AuditReport {
+    pub entries: Vec<AuditEntry>,
+    pub stats: AuditStats,
+}
+
+impl AuditReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a successfully saved file.
+    pub fn record(&mut self, entry: AuditEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Records a carve whose `extract` succeeded but whose file couldn't be
+    /// written to disk.
+    pub fn record_failure(&mut self) {
+        self.stats.failed += 1;
+    }
+
+    /// Counts recovered files per carver extension, in a stable order.
+    fn files_by_extension(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for entry in &self.entries {
+            *counts.entry(entry.extension.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Writes the human-readable `audit.txt` report.
+    pub fn write_text(&self, path: &Path) -> Result<()> {
+        let mut out = String::new();
+        writeln!(out, "rforemost audit report").ok();
+        writeln!(out, "bytes scanned: {}", self.stats.bytes_scanned).ok();
+        writeln!(out, "elapsed: {:.2}s", self.stats.elapsed_secs).ok();
+        writeln!(out, "files recovered: {}", self.entries.len()).ok();
+        writeln!(out, "failed carves: {}", self.stats.failed).ok();
+        writeln!(out).ok();
+        writeln!(out, "files by type:").ok();
+        for (extension, count) in self.files_by_extension() {
+            writeln!(out, "  {extension}: {count}").ok();
+        }
+        writeln!(out).ok();
+        writeln!(out, "offset        size  filename  hash").ok();
+        for entry in &self.entries {
+            write!(out, "{:#010x}  {:>10}  {}", entry.offset, entry.size, entry.filename).ok();
+            if let Some(hash) = &entry.hash {
+                write!(out, "  {hash}").ok();
+            }
+            writeln!(out).ok();
+        }
+
+        let mut f = File::create(path)?;
+        f.write_all(out.as_bytes())?;
+        Ok(())
+    }
+
+    /// Writes the same data as `audit.json`, for tooling that wants to
+    /// dedupe carves across runs by hash rather than parse the text report.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("serializing audit report")?;
+        let mut f = File::create(path)?;
+        f.write_all(json.as_bytes())?;
+        Ok(())
+    }
+}
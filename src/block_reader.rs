@@ -0,0 +1,137 @@
+//! Uniform, random-access byte view over an input image.
+//!
+//! `main`'s scan loop used to hard-require the whole input to be mapped via
+//! `mmap`, which fails on images too large to address and on compressed
+//! images. [`BlockReader`] hides that behind one interface: a raw mmap for
+//! inputs that fit, a buffered 64-bit-seek reader for inputs that don't, and
+//! transparent gzip/zstd/bzip2 decompression (to a spool file) for either.
+
+use anyhow::{Context, Result};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+/// Inputs at or below this size are mapped directly; larger ones fall back
+/// to buffered seeks so scanning isn't bounded by addressable memory.
+const MMAP_SIZE_LIMIT: u64 = 4 * 1024 * 1024 * 1024; // 4 GiB
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+fn detect_compression(magic: &[u8]) -> Option<Compression> {
+    if magic.starts_with(&[0x1F, 0x8B]) {
+        Some(Compression::Gzip)
+    } else if magic.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+        Some(Compression::Zstd)
+    } else if magic.starts_with(b"BZh") {
+        Some(Compression::Bzip2)
+    } else {
+        None
+    }
+}
+
+/// A random-access view over an input image's uncompressed bytes.
+pub enum BlockReader {
+    /// The whole file mapped into the address space (the historical
+    /// behavior). Used for inputs that comfortably fit in memory.
+    Mmap(Mmap),
+    /// A file accessed through buffered, 64-bit seeks. Used for images too
+    /// large to map, and for images decompressed to a spool file.
+    Buffered { file: File, len: u64 },
+}
+
+impl BlockReader {
+    /// Opens `path`, auto-detecting gzip/zstd/bzip2 compression by magic
+    /// bytes and transparently decompressing to a temporary spool file
+    /// before presenting a random-access view over the plain bytes.
+    pub fn open(path: &Path) -> Result<BlockReader> {
+        let mut probe = File::open(path).with_context(|| format!("opening {path:?}"))?;
+        let mut magic = [0u8; 4];
+        let n = std::io::Read::read(&mut probe, &mut magic)?;
+
+        match detect_compression(&magic[..n]) {
+            Some(compression) => Self::open_compressed(path, compression),
+            None => Self::open_plain(path),
+        }
+    }
+
+    fn open_plain(path: &Path) -> Result<BlockReader> {
+        let file = File::open(path).with_context(|| format!("opening {path:?}"))?;
+        let len = file.metadata()?.len();
+        if len <= MMAP_SIZE_LIMIT {
+            let mmap = unsafe { memmap2::MmapOptions::new().map(&file)? };
+            Ok(BlockReader::Mmap(mmap))
+        } else {
+            Ok(BlockReader::Buffered { file, len })
+        }
+    }
+
+    fn open_compressed(path: &Path, compression: Compression) -> Result<BlockReader> {
+        let file = File::open(path).with_context(|| format!("opening {path:?}"))?;
+        let mut spool =
+            tempfile::tempfile().context("creating spool file for decompressed image")?;
+
+        match compression {
+            Compression::Gzip => {
+                let mut decoder = flate2::read::GzDecoder::new(file);
+                std::io::copy(&mut decoder, &mut spool)
+                    .context("decompressing gzip image to spool file")?;
+            }
+            Compression::Zstd => {
+                let mut decoder =
+                    zstd::stream::read::Decoder::new(file).context("opening zstd stream")?;
+                std::io::copy(&mut decoder, &mut spool)
+                    .context("decompressing zstd image to spool file")?;
+            }
+            Compression::Bzip2 => {
+                let mut decoder = bzip2::read::BzDecoder::new(file);
+                std::io::copy(&mut decoder, &mut spool)
+                    .context("decompressing bzip2 image to spool file")?;
+            }
+        }
+
+        let len = spool.seek(SeekFrom::End(0))?;
+        Ok(BlockReader::Buffered { file: spool, len })
+    }
+
+    /// Total length of the uncompressed byte stream.
+    pub fn len(&self) -> u64 {
+        match self {
+            BlockReader::Mmap(mmap) => mmap.len() as u64,
+            BlockReader::Buffered { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads up to `len` bytes starting at `start`, clamped to the end of
+    /// the stream.
+    pub fn read_window(&self, start: u64, len: usize) -> Result<Vec<u8>> {
+        let total = self.len();
+        if start >= total {
+            return Ok(Vec::new());
+        }
+        let actual_len = std::cmp::min(len as u64, total - start) as usize;
+
+        match self {
+            BlockReader::Mmap(mmap) => {
+                let start = start as usize;
+                Ok(mmap[start..start + actual_len].to_vec())
+            }
+            BlockReader::Buffered { file, .. } => {
+                let mut buf = vec![0u8; actual_len];
+                file.read_exact_at(&mut buf, start)
+                    .context("reading scan window")?;
+                Ok(buf)
+            }
+        }
+    }
+}
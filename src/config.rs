@@ -0,0 +1,286 @@
+//! Loader for `foremost.conf`-style signature databases.
+//!
+//! This mirrors the classic foremost config format: one signature per line,
+//! fields separated by whitespace, a line starting with `#` is a comment.
+//! Each line becomes a
+//! [`GenericCarver`] so new formats can be added without recompiling.
+
+use crate::Carver;
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+
+/// A single byte in a header/footer pattern, decoded from the config's
+/// `\xHH` hex escapes and `\?` wildcard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PatternByte {
+    Literal(u8),
+    Wildcard,
+}
+
+/// One parsed line of a signature config, before its patterns are decoded.
+struct ConfigEntry {
+    extension: String,
+    case_sensitive: bool,
+    max_size: usize,
+    header: String,
+    footer: Option<String>,
+    search_from_end: bool,
+}
+
+/// A carver built from a single config line.
+///
+/// `matches_header()` compares against the decoded header pattern (honoring
+/// `\?` wildcards and case sensitivity); `header_magic()` returns the decoded
+/// header's literal bytes, with each wildcard as `0`. `extract()` searches up
+/// to `max_size` bytes for the footer pattern (first match, or last match when
+/// the entry's search mode is `end`), or returns `max_size` bytes, clamped to
+/// the end of the data, if the entry has no footer.
+pub struct GenericCarver {
+    extension: String,
+    case_sensitive: bool,
+    max_size: usize,
+    header: Vec<PatternByte>,
+    footer: Option<Vec<PatternByte>>,
+    search_from_end: bool,
+    header_literal: Vec<u8>,
+}
+
+fn decode_pattern(raw: &str) -> Result<Vec<PatternByte>> {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 1 < bytes.len() {
+            match bytes[i + 1] {
+                b'x' => {
+                    if i + 4 > bytes.len() {
+                        bail!("truncated \\x escape in pattern: {raw:?}");
+                    }
+                    let hex = std::str::from_utf8(&bytes[i + 2..i + 4])
+                        .with_context(|| format!("invalid \\x escape in pattern: {raw:?}"))?;
+                    let byte = u8::from_str_radix(hex, 16)
+                        .with_context(|| format!("invalid \\x escape in pattern: {raw:?}"))?;
+                    out.push(PatternByte::Literal(byte));
+                    i += 4;
+                }
+                b'?' => {
+                    out.push(PatternByte::Wildcard);
+                    i += 2;
+                }
+                b'\\' => {
+                    out.push(PatternByte::Literal(b'\\'));
+                    i += 2;
+                }
+                other => bail!("unknown escape '\\{}' in pattern: {raw:?}", other as char),
+            }
+        } else {
+            out.push(PatternByte::Literal(bytes[i]));
+            i += 1;
+        }
+    }
+    Ok(out)
+}
+
+fn parse_size(raw: &str) -> Result<usize> {
+    let raw = raw.trim();
+    let (digits, multiplier) = match raw.chars().last() {
+        Some('k') | Some('K') => (&raw[..raw.len() - 1], 1024),
+        Some('m') | Some('M') => (&raw[..raw.len() - 1], 1024 * 1024),
+        Some('g') | Some('G') => (&raw[..raw.len() - 1], 1024 * 1024 * 1024),
+        _ => (raw, 1),
+    };
+    let value: usize = digits
+        .parse()
+        .with_context(|| format!("invalid max_size: {raw:?}"))?;
+    Ok(value * multiplier)
+}
+
+fn parse_line(line: &str) -> Result<ConfigEntry> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 4 {
+        bail!("expected at least 4 fields (extension, case_sensitivity, max_size, header), got {line:?}");
+    }
+
+    let extension = fields[0].to_string();
+    let case_sensitive = match fields[1] {
+        "y" | "Y" => true,
+        "n" | "N" => false,
+        other => bail!("case_sensitivity must be y/n, got {other:?}"),
+    };
+    let max_size = parse_size(fields[2])?;
+    let header = fields[3].to_string();
+    let footer = fields.get(4).filter(|f| **f != "-").map(|f| f.to_string());
+    let search_from_end = fields.get(5).is_some_and(|m| *m == "end");
+
+    Ok(ConfigEntry {
+        extension,
+        case_sensitive,
+        max_size,
+        header,
+        footer,
+        search_from_end,
+    })
+}
+
+/// Parses a full config file's contents into entries, skipping blank lines
+/// and `#` comments.
+fn parse_config(contents: &str) -> Result<Vec<ConfigEntry>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_line)
+        .collect()
+}
+
+impl GenericCarver {
+    fn from_entry(entry: ConfigEntry) -> Result<Self> {
+        let header = decode_pattern(&entry.header)?;
+        let footer = entry.footer.as_deref().map(decode_pattern).transpose()?;
+        let header_literal = header
+            .iter()
+            .map(|b| match b {
+                PatternByte::Literal(byte) => *byte,
+                PatternByte::Wildcard => 0,
+            })
+            .collect();
+
+        Ok(GenericCarver {
+            extension: entry.extension,
+            case_sensitive: entry.case_sensitive,
+            max_size: entry.max_size,
+            header,
+            footer,
+            search_from_end: entry.search_from_end,
+            header_literal,
+        })
+    }
+
+    fn byte_matches(&self, pattern_byte: PatternByte, data_byte: u8) -> bool {
+        match pattern_byte {
+            PatternByte::Wildcard => true,
+            PatternByte::Literal(b) if self.case_sensitive => b == data_byte,
+            PatternByte::Literal(b) => b.eq_ignore_ascii_case(&data_byte),
+        }
+    }
+
+    fn pattern_matches_at(&self, pattern: &[PatternByte], data: &[u8], offset: usize) -> bool {
+        offset + pattern.len() <= data.len()
+            && pattern
+                .iter()
+                .zip(&data[offset..offset + pattern.len()])
+                .all(|(p, b)| self.byte_matches(*p, *b))
+    }
+
+    /// Finds the footer pattern within `data`, searching forward for the
+    /// first match or backward for the last, per `search_from_end`.
+    fn find_footer(&self, footer: &[PatternByte], data: &[u8]) -> Option<usize> {
+        if footer.len() > data.len() {
+            return None;
+        }
+        let positions = 0..=data.len() - footer.len();
+        if self.search_from_end {
+            positions.rev().find(|&pos| self.pattern_matches_at(footer, data, pos))
+        } else {
+            positions.into_iter().find(|&pos| self.pattern_matches_at(footer, data, pos))
+        }
+    }
+}
+
+impl Carver for GenericCarver {
+    fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    fn header_magic(&self) -> &[u8] {
+        &self.header_literal
+    }
+
+    fn header_first_bytes(&self) -> Vec<u8> {
+        match self.header.first() {
+            Some(PatternByte::Wildcard) => (0..=255).collect(),
+            Some(PatternByte::Literal(b)) if self.case_sensitive => vec![*b],
+            Some(PatternByte::Literal(b)) => {
+                vec![b.to_ascii_lowercase(), b.to_ascii_uppercase()]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn matches_header(&self, data: &[u8], offset: usize) -> bool {
+        self.pattern_matches_at(&self.header, data, offset)
+    }
+
+    fn max_extract_span(&self) -> usize {
+        self.max_size
+    }
+
+    fn extract(&self, data: &[u8], start_offset: usize) -> Option<usize> {
+        if !self.matches_header(data, start_offset) {
+            return None;
+        }
+
+        let search_start = start_offset + self.header.len();
+        // max_size smaller than the header itself is a config mistake, not
+        // out-of-bounds data; clamp rather than let the slice below panic.
+        let search_end = std::cmp::min(data.len(), start_offset + self.max_size).max(search_start);
+
+        match &self.footer {
+            Some(footer) => {
+                let window = &data[search_start..search_end];
+                self.find_footer(footer, window)
+                    .map(|pos| search_start + pos + footer.len() - start_offset)
+            }
+            None => Some(search_end - start_offset),
+        }
+    }
+}
+
+/// Loads a signature config file and builds a [`GenericCarver`] per entry.
+pub fn load_carvers(path: &Path) -> Result<Vec<GenericCarver>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("reading config file {path:?}"))?;
+    parse_config(&contents)?
+        .into_iter()
+        .map(GenericCarver::from_entry)
+        .collect()
+}
+
+#[cfg(test)]
+mod generic_carver_tests {
+    use super::*;
+
+    fn carver(line: &str) -> GenericCarver {
+        GenericCarver::from_entry(parse_line(line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn extract_finds_footer() {
+        let c = carver(r"tst y 1k \xAA\xBB \xCC\xDD");
+        let data = b"\xAA\xBBhello\xCC\xDDtrailing";
+        assert_eq!(c.extract(data, 0), Some(9)); // up to and including the footer
+    }
+
+    #[test]
+    fn extract_without_footer_uses_max_size() {
+        let c = carver(r"tst y 4 \xAA\xBB -");
+        let data = b"\xAA\xBBhello world";
+        assert_eq!(c.extract(data, 0), Some(4));
+    }
+
+    #[test]
+    fn extract_does_not_panic_when_max_size_is_smaller_than_header() {
+        // A config mistake (max_size shorter than the header pattern)
+        // must not make search_start > search_end and panic on the slice.
+        let c = carver(r"tst y 1 \xAA\xBB\xCC\xDD -");
+        let data = b"\xAA\xBB\xCC\xDDrest";
+        assert_eq!(c.extract(data, 0), Some(4));
+    }
+
+    #[test]
+    fn extract_returns_none_when_footer_is_absent() {
+        let c = carver(r"tst y 1k \xAA\xBB \xCC\xDD");
+        let data = b"\xAA\xBBno footer here";
+        assert_eq!(c.extract(data, 0), None);
+    }
+}
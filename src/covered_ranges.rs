@@ -0,0 +1,97 @@
+//! Tracks byte ranges already claimed by a recovered file so embedded
+//! content (a thumbnail inside a JPEG, an image inside a PDF) isn't carved
+//! out again as a redundant, separate file.
+
+use std::sync::Mutex;
+
+/// A concurrency-safe set of `[start, end)` spans already claimed by a
+/// carved file, kept sorted by `start` so a lookup is a binary search
+/// rather than a linear scan under the lock.
+///
+/// Used two ways: during the parallel scan, [`is_covered`] lets a chunk
+/// skip an offset another chunk already claimed (best-effort, since chunks
+/// run out of order so this can't be authoritative); the caller is expected
+/// to follow up with a final merge pass over every candidate once scanning
+/// completes, since two overlapping carves can still be discovered out of
+/// order.
+///
+/// [`is_covered`]: CoveredRanges::is_covered
+pub struct CoveredRanges {
+    ranges: Mutex<Vec<(u64, u64)>>,
+}
+
+impl CoveredRanges {
+    pub fn new() -> Self {
+        CoveredRanges {
+            ranges: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// True if `offset` falls inside any range recorded so far.
+    ///
+    /// Binary searches for the latest-starting range at or before `offset`
+    /// and checks only that one candidate, which is O(log n) instead of
+    /// scanning every range. Ranges aren't required to be disjoint here, so
+    /// this can occasionally miss an `offset` that's actually covered by an
+    /// earlier, wider range (e.g. a big carve recorded before a nested one
+    /// starting later but ending sooner) — acceptable since this check is
+    /// already documented as best-effort, and a miss just means the final
+    /// merge pass does the deduplication instead. It never reports an
+    /// uncovered offset as covered.
+    pub fn is_covered(&self, offset: u64) -> bool {
+        let ranges = self.ranges.lock().unwrap();
+        match ranges.partition_point(|&(start, _)| start <= offset) {
+            0 => false,
+            i => {
+                let (start, end) = ranges[i - 1];
+                offset >= start && offset < end
+            }
+        }
+    }
+
+    /// Records a newly discovered carve's span, keeping `ranges` sorted by
+    /// `start`.
+    pub fn record(&self, start: u64, end: u64) {
+        let mut ranges = self.ranges.lock().unwrap();
+        let i = ranges.partition_point(|&(s, _)| s <= start);
+        ranges.insert(i, (start, end));
+    }
+}
+
+impl Default for CoveredRanges {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_before_any_range_is_not_covered() {
+        let ranges = CoveredRanges::new();
+        ranges.record(100, 200);
+        assert!(!ranges.is_covered(50));
+    }
+
+    #[test]
+    fn offset_inside_a_range_is_covered() {
+        let ranges = CoveredRanges::new();
+        ranges.record(100, 200);
+        assert!(ranges.is_covered(100));
+        assert!(ranges.is_covered(150));
+        assert!(!ranges.is_covered(200)); // end is exclusive
+    }
+
+    #[test]
+    fn lookup_works_regardless_of_insertion_order() {
+        let ranges = CoveredRanges::new();
+        ranges.record(500, 600);
+        ranges.record(100, 200);
+        ranges.record(300, 400);
+        assert!(ranges.is_covered(350));
+        assert!(ranges.is_covered(150));
+        assert!(!ranges.is_covered(250));
+    }
+}
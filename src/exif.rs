@@ -0,0 +1,368 @@
+//! EXIF-aware naming and metadata sidecars for carved JPEG/TIFF files.
+//!
+//! A JPEG stores EXIF in its APP1 segment; a TIFF's own IFD0 *is* the EXIF
+//! structure. Both are the same byte-order-tagged IFD chain a [`TiffCarver`]
+//! already knows how to walk, so this module reuses its bounds-checked,
+//! endianness-aware tag readers rather than re-deriving them.
+//!
+//! [`TiffCarver`]: crate::TiffCarver
+
+use crate::{tiff_read_tag_values, tiff_read_u16, tiff_read_u32, tiff_type_size};
+use anyhow::{Context, Result};
+use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+const TAG_MAKE: u16 = 271;
+const TAG_MODEL: u16 = 272;
+const TAG_DATE_TIME: u16 = 306;
+const TAG_EXIF_IFD_POINTER: u16 = 34665;
+const TAG_GPS_IFD_POINTER: u16 = 34853;
+const TAG_DATE_TIME_ORIGINAL: u16 = 36867;
+const TAG_GPS_LATITUDE_REF: u16 = 1;
+const TAG_GPS_LATITUDE: u16 = 2;
+const TAG_GPS_LONGITUDE_REF: u16 = 3;
+const TAG_GPS_LONGITUDE: u16 = 4;
+
+/// Decoded EXIF fields for a single carved photo.
+#[derive(Debug, Default, Serialize)]
+pub struct ExifMetadata {
+    pub date_time_original: Option<String>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub gps_latitude: Option<f64>,
+    pub gps_longitude: Option<f64>,
+}
+
+impl ExifMetadata {
+    fn is_empty(&self) -> bool {
+        self.date_time_original.is_none()
+            && self.make.is_none()
+            && self.model.is_none()
+            && self.gps_latitude.is_none()
+            && self.gps_longitude.is_none()
+    }
+
+    /// Reformats `DateTimeOriginal` (EXIF's `YYYY:MM:DD HH:MM:SS`) into the
+    /// `YYYY-MM-DD_HHMMSS` form used in carved filenames.
+    ///
+    /// The raw value comes straight from the scanned (untrusted) image, so
+    /// beyond the length check this also requires every byte to be an
+    /// ASCII digit or `:` before it's allowed anywhere near a filename —
+    /// otherwise a crafted tag (e.g. containing `/`) could escape the
+    /// output directory once joined with it.
+    fn formatted_timestamp(&self) -> Option<String> {
+        let raw = self.date_time_original.as_ref()?;
+        let (date, time) = raw.split_once(' ')?;
+        if date.len() != 10 || time.len() != 8 {
+            return None;
+        }
+        if !is_digits_and_colons(date) || !is_digits_and_colons(time) {
+            return None;
+        }
+        Some(format!("{}_{}", date.replace(':', "-"), time.replace(':', "")))
+    }
+}
+
+/// True if every byte is an ASCII digit or `:`, the only characters a
+/// well-formed EXIF date or time field may contain.
+fn is_digits_and_colons(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_digit() || b == b':')
+}
+
+/// A single decoded IFD entry: `(tag, field_type, count, entry_start)`.
+type IfdEntry = (u16, u16, u32, usize);
+
+/// Parses one IFD's entries, bounds-checking the directory itself.
+/// Returns `None` on a truncated or out-of-bounds directory.
+fn read_ifd_entries(data: &[u8], ifd_offset: usize, big_endian: bool) -> Option<Vec<IfdEntry>> {
+    if ifd_offset + 2 > data.len() {
+        return None;
+    }
+    let entry_count = tiff_read_u16(data, ifd_offset, big_endian) as usize;
+    let entries_end = ifd_offset + 2 + entry_count * 12;
+    if entries_end > data.len() {
+        return None;
+    }
+
+    Some(
+        (0..entry_count)
+            .map(|i| {
+                let entry_start = ifd_offset + 2 + i * 12;
+                let tag = tiff_read_u16(data, entry_start, big_endian);
+                let field_type = tiff_read_u16(data, entry_start + 2, big_endian);
+                let count = tiff_read_u32(data, entry_start + 4, big_endian);
+                (tag, field_type, count, entry_start)
+            })
+            .collect(),
+    )
+}
+
+/// Reads an ASCII tag's value, trimming the trailing NUL terminator.
+///
+/// `base_offset` is added to an out-of-line value's offset, since TIFF
+/// offsets are relative to the start of the TIFF header, not to `data[0]`.
+fn read_ascii(
+    data: &[u8],
+    base_offset: usize,
+    entry_start: usize,
+    field_type: u16,
+    count: u32,
+    big_endian: bool,
+) -> Option<String> {
+    if field_type != 2 {
+        return None;
+    }
+    let type_size = tiff_type_size(field_type)?;
+    let data_len = type_size.checked_mul(count as usize)?;
+    let start = if data_len <= 4 {
+        entry_start + 8
+    } else {
+        base_offset + tiff_read_u32(data, entry_start + 8, big_endian) as usize
+    };
+    let end = start.checked_add(data_len)?;
+    let bytes = data.get(start..end)?;
+    let trimmed = bytes.split(|&b| b == 0).next().unwrap_or(bytes);
+    std::str::from_utf8(trimmed).ok().map(str::to_string)
+}
+
+fn read_rational(data: &[u8], pos: usize, big_endian: bool) -> Option<f64> {
+    let bytes = data.get(pos..pos + 8)?;
+    let numerator = if big_endian {
+        BigEndian::read_u32(&bytes[0..4])
+    } else {
+        byteorder::LittleEndian::read_u32(&bytes[0..4])
+    } as f64;
+    let denominator = if big_endian {
+        BigEndian::read_u32(&bytes[4..8])
+    } else {
+        byteorder::LittleEndian::read_u32(&bytes[4..8])
+    } as f64;
+    (denominator != 0.0).then_some(numerator / denominator)
+}
+
+/// Reads a GPS coordinate stored as 3 RATIONALs (degrees, minutes, seconds)
+/// and returns it as decimal degrees.
+fn read_dms(
+    data: &[u8],
+    base_offset: usize,
+    entry_start: usize,
+    field_type: u16,
+    count: u32,
+    big_endian: bool,
+) -> Option<f64> {
+    if field_type != 5 || count != 3 {
+        return None;
+    }
+    let type_size = tiff_type_size(field_type)?;
+    let data_len = type_size.checked_mul(3)?;
+    let start = base_offset + tiff_read_u32(data, entry_start + 8, big_endian) as usize;
+    if start.checked_add(data_len)? > data.len() {
+        return None;
+    }
+    let degrees = read_rational(data, start, big_endian)?;
+    let minutes = read_rational(data, start + 8, big_endian)?;
+    let seconds = read_rational(data, start + 16, big_endian)?;
+    Some(degrees + minutes / 60.0 + seconds / 3600.0)
+}
+
+fn apply_gps(
+    metadata: &mut ExifMetadata,
+    data: &[u8],
+    tiff_start: usize,
+    entries: &[IfdEntry],
+    big_endian: bool,
+) {
+    let mut latitude = None;
+    let mut latitude_ref = None;
+    let mut longitude = None;
+    let mut longitude_ref = None;
+
+    for &(tag, field_type, count, entry_start) in entries {
+        match tag {
+            TAG_GPS_LATITUDE_REF => {
+                latitude_ref = read_ascii(data, tiff_start, entry_start, field_type, count, big_endian)
+            }
+            TAG_GPS_LATITUDE => latitude = read_dms(data, tiff_start, entry_start, field_type, count, big_endian),
+            TAG_GPS_LONGITUDE_REF => {
+                longitude_ref = read_ascii(data, tiff_start, entry_start, field_type, count, big_endian)
+            }
+            TAG_GPS_LONGITUDE => longitude = read_dms(data, tiff_start, entry_start, field_type, count, big_endian),
+            _ => {}
+        }
+    }
+
+    if let Some(mut value) = latitude {
+        if latitude_ref.as_deref() == Some("S") {
+            value = -value;
+        }
+        metadata.gps_latitude = Some(value);
+    }
+    if let Some(mut value) = longitude {
+        if longitude_ref.as_deref() == Some("W") {
+            value = -value;
+        }
+        metadata.gps_longitude = Some(value);
+    }
+}
+
+/// Walks a TIFF-structured IFD0 (and its Exif/GPS sub-IFDs, if present)
+/// starting at `tiff_start`, which must point at the 8-byte TIFF header
+/// (`II`/`MM` + magic + first-IFD offset). All offsets inside the structure
+/// are relative to `tiff_start`, matching both a bare TIFF file and a
+/// JPEG's embedded EXIF block.
+fn parse_tiff_structure(data: &[u8], tiff_start: usize) -> Option<ExifMetadata> {
+    if tiff_start + 8 > data.len() {
+        return None;
+    }
+    let big_endian = match &data[tiff_start..tiff_start + 2] {
+        b"II" => false,
+        b"MM" => true,
+        _ => return None,
+    };
+    if tiff_read_u16(data, tiff_start + 2, big_endian) != 42 {
+        return None;
+    }
+
+    let ifd0_offset = tiff_start + tiff_read_u32(data, tiff_start + 4, big_endian) as usize;
+    let ifd0 = read_ifd_entries(data, ifd0_offset, big_endian)?;
+
+    let mut metadata = ExifMetadata::default();
+
+    for &(tag, field_type, count, entry_start) in &ifd0 {
+        match tag {
+            TAG_MAKE => metadata.make = read_ascii(data, tiff_start, entry_start, field_type, count, big_endian),
+            TAG_MODEL => metadata.model = read_ascii(data, tiff_start, entry_start, field_type, count, big_endian),
+            TAG_DATE_TIME => {
+                metadata.date_time_original = metadata
+                    .date_time_original
+                    .or_else(|| read_ascii(data, tiff_start, entry_start, field_type, count, big_endian));
+            }
+            TAG_EXIF_IFD_POINTER => {
+                if let Some(offset) =
+                    tiff_read_tag_values(data, tiff_start, entry_start, field_type, count, big_endian)
+                        .and_then(|values| values.first().copied())
+                {
+                    let exif_ifd_offset = tiff_start + offset as usize;
+                    if let Some(exif_entries) = read_ifd_entries(data, exif_ifd_offset, big_endian) {
+                        for &(tag, field_type, count, entry_start) in &exif_entries {
+                            if tag == TAG_DATE_TIME_ORIGINAL {
+                                metadata.date_time_original =
+                                    read_ascii(data, tiff_start, entry_start, field_type, count, big_endian);
+                            }
+                        }
+                    }
+                }
+            }
+            TAG_GPS_IFD_POINTER => {
+                if let Some(offset) =
+                    tiff_read_tag_values(data, tiff_start, entry_start, field_type, count, big_endian)
+                        .and_then(|values| values.first().copied())
+                {
+                    let gps_ifd_offset = tiff_start + offset as usize;
+                    if let Some(gps_entries) = read_ifd_entries(data, gps_ifd_offset, big_endian) {
+                        apply_gps(&mut metadata, data, tiff_start, &gps_entries, big_endian);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (!metadata.is_empty()).then_some(metadata)
+}
+
+/// Extracts EXIF metadata from a carved JPEG's APP1 segment.
+fn parse_jpeg_exif(data: &[u8], start_offset: usize) -> Option<ExifMetadata> {
+    if data.get(start_offset..start_offset + 2)? != [0xFF, 0xD8] {
+        return None;
+    }
+
+    let mut pos = start_offset + 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            pos += 2;
+            continue;
+        }
+        if marker == 0xDA {
+            break; // Start of scan: no more APPn segments can follow.
+        }
+
+        let segment_len = BigEndian::read_u16(&data[pos + 2..pos + 4]) as usize;
+        if marker == 0xE1 && pos + 10 <= data.len() && &data[pos + 4..pos + 10] == b"Exif\0\0" {
+            return parse_tiff_structure(data, pos + 10);
+        }
+        pos += 2 + segment_len;
+    }
+    None
+}
+
+/// Extracts EXIF metadata from a carved JPEG or TIFF file at `start_offset`.
+pub fn extract_exif(data: &[u8], start_offset: usize, extension: &str) -> Option<ExifMetadata> {
+    match extension {
+        "jpg" => parse_jpeg_exif(data, start_offset),
+        "tif" => parse_tiff_structure(data, start_offset),
+        _ => None,
+    }
+}
+
+/// Builds a carved file's name: `{DateTimeOriginal}_{offset:08}.{ext}` when
+/// EXIF metadata carries a usable timestamp, otherwise falling back to the
+/// offset-only `file_{offset:08}.{ext}` convention.
+pub fn carved_filename(metadata: Option<&ExifMetadata>, offset: u64, extension: &str) -> String {
+    match metadata.and_then(ExifMetadata::formatted_timestamp) {
+        Some(timestamp) => format!("{timestamp}_{offset:08}.{extension}"),
+        None => format!("file_{offset:08}.{extension}"),
+    }
+}
+
+/// Writes a carved file's decoded EXIF fields as a `.json` sidecar.
+pub fn write_sidecar(path: &Path, metadata: &ExifMetadata) -> Result<()> {
+    let json = serde_json::to_string_pretty(metadata).context("serializing EXIF metadata")?;
+    let mut f = File::create(path)?;
+    f.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod carved_filename_tests {
+    use super::*;
+
+    #[test]
+    fn uses_timestamp_when_well_formed() {
+        let metadata = ExifMetadata {
+            date_time_original: Some("2021:05:06 13:07:09".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            carved_filename(Some(&metadata), 4096, "jpg"),
+            "2021-05-06_130709_00004096.jpg"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_offset_only_name_when_fields_are_missing() {
+        assert_eq!(carved_filename(None, 4096, "jpg"), "file_00004096.jpg");
+    }
+
+    #[test]
+    fn rejects_non_digit_bytes_that_would_escape_the_output_directory() {
+        // A crafted DateTimeOriginal tag that's the right length but not
+        // actually digits/colons (e.g. path traversal) must not make it
+        // into the filename.
+        let metadata = ExifMetadata {
+            date_time_original: Some("../../../A AAAAAAAA".to_string()),
+            ..Default::default()
+        };
+        let filename = carved_filename(Some(&metadata), 4096, "jpg");
+        assert_eq!(filename, "file_00004096.jpg");
+        assert!(!filename.contains(".."));
+        assert!(!filename.contains('/'));
+    }
+}
@@ -1,8 +1,21 @@
 use anyhow::Result;
-use byteorder::{BigEndian, ByteOrder};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
+use std::sync::OnceLock;
+
+mod audit;
+mod block_reader;
+mod config;
+mod covered_ranges;
+mod exif;
+pub use audit::{AuditEntry, AuditReport, HashAlgorithm, save_file_with_hash};
+pub use block_reader::BlockReader;
+pub use config::{GenericCarver, load_carvers};
+pub use covered_ranges::CoveredRanges;
+pub use exif::{ExifMetadata, carved_filename, extract_exif, write_sidecar};
 
 /// The core trait for file recovery.
 ///
@@ -15,6 +28,16 @@ pub trait Carver: Send + Sync {
     /// Returns the magic bytes used to identify the file format's header.
     fn header_magic(&self) -> &[u8];
 
+    /// Returns every possible first byte of this format's header.
+    ///
+    /// Most formats have a single fixed magic, so the default is just the
+    /// first byte of `header_magic()`. Formats with more than one valid
+    /// header (e.g. TIFF's `II`/`MM` byte order mark) override this so the
+    /// quick first-byte scan in the caller doesn't miss them.
+    fn header_first_bytes(&self) -> Vec<u8> {
+        vec![self.header_magic()[0]]
+    }
+
     /// Checks if the data at the given offset matches the format's header.
     fn matches_header(&self, data: &[u8], offset: usize) -> bool {
         let magic = self.header_magic();
@@ -25,6 +48,18 @@ pub trait Carver: Send + Sync {
     ///
     /// Returns the total size from `start_offset` if a valid file is found.
     fn extract(&self, data: &[u8], start_offset: usize) -> Option<usize>;
+
+    /// Upper bound on how many bytes past a header this carver's `extract`
+    /// may need to see to determine a file's size.
+    ///
+    /// The scanner uses the largest value across all registered carvers as
+    /// the overlap between adjacent scan windows, so a carve that starts
+    /// near the end of one window still has its full header-to-footer span
+    /// available to read. The default is a generous upper bound for
+    /// trailer-scanning carvers with no intrinsic limit.
+    fn max_extract_span(&self) -> usize {
+        16 * 1024 * 1024
+    }
 }
 
 /// JPEG File Carver implementation.
@@ -76,6 +111,114 @@ impl Carver for JpegCarver {
     }
 }
 
+/// Table-driven CRC-32 (the zlib/PNG polynomial, `0xEDB88320`), computed
+/// once and cached for the life of the process.
+fn crc32_table() -> &'static [u32; 256] {
+    static TABLE: OnceLock<[u32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u32; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 != 0 {
+                    0xEDB88320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+        table
+    })
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let table = crc32_table();
+    let mut crc = 0xFFFFFFFFu32;
+    for &byte in data {
+        let index = ((crc ^ byte as u32) & 0xFF) as usize;
+        crc = table[index] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFFFFFF
+}
+
+/// Upper bound on an IHDR width or height: larger than any real image ever
+/// needs to be, so it catches garbage dimensions read from random bytes.
+const PNG_MAX_DIMENSION: u32 = 1 << 20;
+
+/// Validates an IHDR chunk's dimensions and bit-depth/color-type
+/// combination against the PNG spec (section 11.2.2).
+fn png_validate_ihdr(ihdr_data: &[u8]) -> bool {
+    if ihdr_data.len() != 13 {
+        return false;
+    }
+
+    let width = BigEndian::read_u32(&ihdr_data[0..4]);
+    let height = BigEndian::read_u32(&ihdr_data[4..8]);
+    if width == 0 || height == 0 || width > PNG_MAX_DIMENSION || height > PNG_MAX_DIMENSION {
+        return false;
+    }
+
+    let bit_depth = ihdr_data[8];
+    let color_type = ihdr_data[9];
+    let valid_depths: &[u8] = match color_type {
+        0 => &[1, 2, 4, 8, 16], // grayscale
+        2 => &[8, 16],          // truecolor
+        3 => &[1, 2, 4, 8],     // indexed
+        4 => &[8, 16],          // grayscale + alpha
+        6 => &[8, 16],          // truecolor + alpha
+        _ => return false,
+    };
+    valid_depths.contains(&bit_depth)
+}
+
+#[cfg(test)]
+mod png_ihdr_tests {
+    use super::*;
+
+    fn ihdr(width: u32, height: u32, bit_depth: u8, color_type: u8) -> Vec<u8> {
+        let mut data = Vec::with_capacity(13);
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data.push(bit_depth);
+        data.push(color_type);
+        data.extend_from_slice(&[0, 0, 0]); // compression, filter, interlace
+        data
+    }
+
+    #[test]
+    fn accepts_valid_truecolor_ihdr() {
+        assert!(png_validate_ihdr(&ihdr(100, 100, 8, 2)));
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        assert!(!png_validate_ihdr(&ihdr(100, 100, 8, 2)[..12]));
+    }
+
+    #[test]
+    fn rejects_zero_dimensions() {
+        assert!(!png_validate_ihdr(&ihdr(0, 100, 8, 2)));
+        assert!(!png_validate_ihdr(&ihdr(100, 0, 8, 2)));
+    }
+
+    #[test]
+    fn rejects_dimensions_past_sane_bound() {
+        assert!(!png_validate_ihdr(&ihdr(PNG_MAX_DIMENSION + 1, 100, 8, 2)));
+    }
+
+    #[test]
+    fn rejects_invalid_bit_depth_for_color_type() {
+        // Truecolor (color_type 2) only allows bit depths 8 and 16.
+        assert!(!png_validate_ihdr(&ihdr(100, 100, 4, 2)));
+    }
+
+    #[test]
+    fn rejects_unknown_color_type() {
+        assert!(!png_validate_ihdr(&ihdr(100, 100, 8, 5)));
+    }
+}
+
 /// PNG File Carver implementation.
 pub struct PngCarver;
 
@@ -94,20 +237,38 @@ impl Carver for PngCarver {
         }
 
         let mut pos = start_offset + 8; // Skip PNG signature
-        while pos + 8 <= data.len() {
+        let mut is_first_chunk = true;
+
+        loop {
+            if pos + 8 > data.len() {
+                return None;
+            }
             let length = BigEndian::read_u32(&data[pos..pos + 4]) as usize;
             let chunk_type = &data[pos + 4..pos + 8];
             let chunk_total = 12 + length; // 4 (len) + 4 (type) + length + 4 (crc)
+            if pos.checked_add(chunk_total)? > data.len() {
+                return None;
+            }
+
+            let crc_offset = pos + 8 + length;
+            let expected_crc = BigEndian::read_u32(&data[crc_offset..crc_offset + 4]);
+            let actual_crc = crc32(&data[pos + 4..crc_offset]);
+            if actual_crc != expected_crc {
+                return None; // Corrupt chunk: not a real PNG.
+            }
+
+            if is_first_chunk {
+                if chunk_type != b"IHDR" || !png_validate_ihdr(&data[pos + 8..pos + 8 + length]) {
+                    return None;
+                }
+                is_first_chunk = false;
+            }
 
             pos += chunk_total;
             if chunk_type == b"IEND" {
                 return Some(pos - start_offset);
             }
-            if pos > data.len() {
-                break;
-            }
         }
-        None
     }
 }
 
@@ -146,6 +307,10 @@ impl Carver for GifCarver {
 /// PDF File Carver implementation.
 pub struct PdfCarver;
 
+/// PDFs are scanned for a trailing `%%EOF` only within this many bytes of
+/// the header; incremental updates past this point are not recovered.
+const PDF_SEARCH_LIMIT: usize = 10 * 1024 * 1024;
+
 impl Carver for PdfCarver {
     fn extension(&self) -> &str {
         "pdf"
@@ -155,6 +320,10 @@ impl Carver for PdfCarver {
         b"%PDF"
     }
 
+    fn max_extract_span(&self) -> usize {
+        PDF_SEARCH_LIMIT
+    }
+
     fn extract(&self, data: &[u8], start_offset: usize) -> Option<usize> {
         if !self.matches_header(data, start_offset) {
             return None;
@@ -162,8 +331,7 @@ impl Carver for PdfCarver {
 
         // PDF carving is complex due to incremental updates.
         // We look for the last %%EOF within a 10MB window.
-        let limit = 10 * 1024 * 1024;
-        let end = std::cmp::min(data.len(), start_offset + limit);
+        let end = std::cmp::min(data.len(), start_offset + PDF_SEARCH_LIMIT);
         let search_range = &data[start_offset..end];
 
         let trailer = b"%%EOF";
@@ -176,9 +344,272 @@ impl Carver for PdfCarver {
     }
 }
 
+/// TIFF File Carver implementation.
+///
+/// TIFF has no trailer, so the true end of the file can only be found by
+/// walking the Image File Directory (IFD) chain and taking the furthest
+/// extent referenced by any directory, tag value, or strip/tile of image
+/// data.
+pub struct TiffCarver;
+
+/// TIFF's true extent is driven by strip/tile data, not a trailer, and
+/// multi-page or uncompressed images can legitimately run well past the
+/// [`Carver::max_extract_span`] default meant for trailer-scanning formats.
+const TIFF_MAX_EXTENT: usize = 512 * 1024 * 1024;
+
+/// Returns the size in bytes of a single value of the given TIFF field type,
+/// or `None` if the type is unrecognized.
+pub(crate) fn tiff_type_size(field_type: u16) -> Option<usize> {
+    Some(match field_type {
+        1 | 2 | 6 | 7 => 1,             // BYTE, ASCII, SBYTE, UNDEFINED
+        3 | 8 => 2,                     // SHORT, SSHORT
+        4 | 9 | 11 => 4,                // LONG, SLONG, FLOAT
+        5 | 10 | 12 => 8,               // RATIONAL, SRATIONAL, DOUBLE
+        _ => return None,
+    })
+}
+
+pub(crate) fn tiff_read_u16(data: &[u8], pos: usize, big_endian: bool) -> u16 {
+    if big_endian {
+        BigEndian::read_u16(&data[pos..pos + 2])
+    } else {
+        LittleEndian::read_u16(&data[pos..pos + 2])
+    }
+}
+
+pub(crate) fn tiff_read_u32(data: &[u8], pos: usize, big_endian: bool) -> u32 {
+    if big_endian {
+        BigEndian::read_u32(&data[pos..pos + 4])
+    } else {
+        LittleEndian::read_u32(&data[pos..pos + 4])
+    }
+}
+
+/// Reads the `count` values of an IFD entry (inline or out-of-line) as
+/// `u64`s. Only SHORT and LONG are supported since those are the only types
+/// used by the strip/tile offset and byte-count tags this carver cares
+/// about.
+///
+/// `base_offset` is added to out-of-line value offsets: TIFF offsets are
+/// always relative to the start of the TIFF header, which for a carved file
+/// is wherever that header landed in `data`, not byte 0.
+pub(crate) fn tiff_read_tag_values(
+    data: &[u8],
+    base_offset: usize,
+    entry_start: usize,
+    field_type: u16,
+    count: u32,
+    big_endian: bool,
+) -> Option<Vec<u64>> {
+    if field_type != 3 && field_type != 4 {
+        return None;
+    }
+    let type_size = tiff_type_size(field_type)?;
+    let count = count as usize;
+    let data_len = type_size.checked_mul(count)?;
+
+    let values_start = if data_len <= 4 {
+        entry_start + 8
+    } else {
+        let offset = base_offset + tiff_read_u32(data, entry_start + 8, big_endian) as usize;
+        offset.checked_add(data_len).filter(|&end| end <= data.len())?;
+        offset
+    };
+
+    (0..count)
+        .map(|i| {
+            let pos = values_start + i * type_size;
+            if pos + type_size > data.len() {
+                return None;
+            }
+            Some(match field_type {
+                3 => tiff_read_u16(data, pos, big_endian) as u64,
+                4 => tiff_read_u32(data, pos, big_endian) as u64,
+                _ => unreachable!(),
+            })
+        })
+        .collect()
+}
+
+impl Carver for TiffCarver {
+    fn extension(&self) -> &str {
+        "tif"
+    }
+
+    fn header_magic(&self) -> &[u8] {
+        b"II"
+    }
+
+    fn header_first_bytes(&self) -> Vec<u8> {
+        vec![b'I', b'M']
+    }
+
+    fn matches_header(&self, data: &[u8], offset: usize) -> bool {
+        offset + 8 <= data.len()
+            && (&data[offset..offset + 2] == b"II" || &data[offset..offset + 2] == b"MM")
+    }
+
+    fn max_extract_span(&self) -> usize {
+        TIFF_MAX_EXTENT
+    }
+
+    fn extract(&self, data: &[u8], start_offset: usize) -> Option<usize> {
+        if !self.matches_header(data, start_offset) {
+            return None;
+        }
+
+        let big_endian = &data[start_offset..start_offset + 2] == b"MM";
+        let magic = tiff_read_u16(data, start_offset + 2, big_endian);
+        if magic == 43 {
+            return None; // BigTIFF is not supported.
+        }
+        if magic != 42 {
+            return None;
+        }
+
+        // Every offset inside a TIFF (the first IFD, an entry's out-of-line
+        // data, the next-IFD pointer, strip/tile offsets) is relative to
+        // `start_offset`, not to byte 0 of `data`, since the header can land
+        // anywhere in a carved image.
+        let mut max_extent = start_offset + 8;
+        let mut next_ifd_relative = tiff_read_u32(data, start_offset + 4, big_endian) as usize;
+        let mut visited = HashSet::new();
+
+        while next_ifd_relative != 0 {
+            if !visited.insert(next_ifd_relative) {
+                return None; // Cyclic IFD chain.
+            }
+
+            let ifd_offset = start_offset.checked_add(next_ifd_relative)?;
+            if ifd_offset + 2 > data.len() {
+                return None;
+            }
+            let entry_count = tiff_read_u16(data, ifd_offset, big_endian) as usize;
+            let entries_end = ifd_offset + 2 + entry_count * 12;
+            if entries_end + 4 > data.len() {
+                return None;
+            }
+            max_extent = max_extent.max(entries_end + 4);
+
+            let mut strip_offsets = None;
+            let mut strip_byte_counts = None;
+            let mut tile_offsets = None;
+            let mut tile_byte_counts = None;
+
+            for i in 0..entry_count {
+                let entry_start = ifd_offset + 2 + i * 12;
+                let tag = tiff_read_u16(data, entry_start, big_endian);
+                let field_type = tiff_read_u16(data, entry_start + 2, big_endian);
+                let count = tiff_read_u32(data, entry_start + 4, big_endian);
+
+                if let Some(type_size) = tiff_type_size(field_type) {
+                    let data_len = type_size.checked_mul(count as usize)?;
+                    if data_len > 4 {
+                        let value_offset =
+                            start_offset + tiff_read_u32(data, entry_start + 8, big_endian) as usize;
+                        let extent = value_offset.checked_add(data_len)?;
+                        if extent > data.len() {
+                            return None;
+                        }
+                        max_extent = max_extent.max(extent);
+                    }
+                }
+
+                match tag {
+                    273 => {
+                        strip_offsets =
+                            tiff_read_tag_values(data, start_offset, entry_start, field_type, count, big_endian)
+                    }
+                    279 => {
+                        strip_byte_counts =
+                            tiff_read_tag_values(data, start_offset, entry_start, field_type, count, big_endian)
+                    }
+                    324 => {
+                        tile_offsets =
+                            tiff_read_tag_values(data, start_offset, entry_start, field_type, count, big_endian)
+                    }
+                    325 => {
+                        tile_byte_counts =
+                            tiff_read_tag_values(data, start_offset, entry_start, field_type, count, big_endian)
+                    }
+                    _ => {}
+                }
+            }
+
+            for (offsets, byte_counts) in [
+                (strip_offsets, strip_byte_counts),
+                (tile_offsets, tile_byte_counts),
+            ] {
+                if let (Some(offsets), Some(byte_counts)) = (offsets, byte_counts) {
+                    for (offset, len) in offsets.iter().zip(byte_counts.iter()) {
+                        let extent = (start_offset as u64).checked_add(*offset)?.checked_add(*len)?;
+                        if extent > data.len() as u64 {
+                            return None;
+                        }
+                        max_extent = max_extent.max(extent as usize);
+                    }
+                }
+            }
+
+            next_ifd_relative = tiff_read_u32(data, entries_end, big_endian) as usize;
+        }
+
+        Some(max_extent - start_offset)
+    }
+}
+
 /// Saves the carved data to the specified path.
 pub fn save_file(path: &Path, data: &[u8]) -> Result<()> {
     let mut f = File::create(path)?;
     f.write_all(data)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tiff_carver_tests {
+    use super::*;
+
+    /// A minimal valid little-endian TIFF: header + one empty IFD (no
+    /// entries, no further IFDs, no strip/tile data).
+    fn minimal_tiff() -> Vec<u8> {
+        let mut data = vec![b'I', b'I', 42, 0, 8, 0, 0, 0]; // header, first IFD at 8
+        data.extend_from_slice(&[0, 0]); // entry_count = 0
+        data.extend_from_slice(&[0, 0, 0, 0]); // next_ifd = 0
+        data
+    }
+
+    #[test]
+    fn extract_minimal_tiff_returns_full_extent() {
+        let data = minimal_tiff();
+        assert_eq!(TiffCarver.extract(&data, 0), Some(data.len()));
+    }
+
+    #[test]
+    fn extract_rejects_truncated_header() {
+        let data = vec![b'I', b'I', 42, 0, 8, 0, 0]; // one byte short of a header
+        assert_eq!(TiffCarver.extract(&data, 0), None);
+    }
+
+    #[test]
+    fn extract_rejects_bigtiff_magic() {
+        let mut data = minimal_tiff();
+        data[2] = 43; // BigTIFF magic, unsupported
+        assert_eq!(TiffCarver.extract(&data, 0), None);
+    }
+
+    #[test]
+    fn extract_rejects_ifd_running_past_end_of_data() {
+        let mut data = minimal_tiff();
+        data.truncate(10); // cuts off the next-IFD pointer
+        assert_eq!(TiffCarver.extract(&data, 0), None);
+    }
+
+    #[test]
+    fn extract_rejects_cyclic_ifd_chain() {
+        let mut data = minimal_tiff();
+        // Point the "next IFD" field back at the IFD that's already being
+        // walked, instead of terminating with 0.
+        data[10..14].copy_from_slice(&8u32.to_le_bytes());
+        assert_eq!(TiffCarver.extract(&data, 0), None);
+    }
+}
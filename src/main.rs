@@ -1,11 +1,15 @@
 use anyhow::Result;
 use clap::Parser;
-use memmap2::MmapOptions;
 use rayon::prelude::*;
-use rforemost::{Carver, GifCarver, JpegCarver, PdfCarver, PngCarver, save_file};
-use std::fs::{self, File};
+use rforemost::{
+    AuditEntry, AuditReport, BlockReader, Carver, CoveredRanges, GifCarver, HashAlgorithm,
+    JpegCarver, PdfCarver, PngCarver, TiffCarver, carved_filename, extract_exif, load_carvers,
+    save_file_with_hash, write_sidecar,
+};
+use std::fs;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 /// A high-performance Rust implementation of the foremost file carving tool.
 #[derive(Parser)]
@@ -26,9 +30,73 @@ struct Args {
     /// Number of threads to use (defaults to CPU count)
     #[arg(short, long)]
     threads: Option<usize>,
+
+    /// Path to a foremost.conf-style signature config for additional, custom carvers
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Size in bytes of each parallel scan window
+    #[arg(long, default_value_t = 64 * 1024 * 1024, value_parser = parse_window_size)]
+    window_size: u64,
+
+    /// Decode EXIF metadata from carved JPEG/TIFF files, name them by
+    /// DateTimeOriginal, and emit a .json sidecar per file
+    #[arg(long)]
+    extract_metadata: bool,
+
+    /// Also carve files found nested inside another recovered file (e.g. a
+    /// thumbnail inside a JPEG), instead of suppressing them
+    #[arg(long)]
+    carve_nested: bool,
+
+    /// Hash each carved file's content for the audit report, trading scan
+    /// speed for integrity verification / cross-run dedup
+    #[arg(long, value_enum, default_value = "none")]
+    hash: HashAlgorithm,
+
+    /// Also write the audit report as machine-readable audit.json
+    #[arg(long)]
+    audit_json: bool,
+}
+
+/// Parses `--window-size`, rejecting 0 (which would make the scan's
+/// `step_by` panic) rather than letting a bad CLI flag crash the process.
+fn parse_window_size(raw: &str) -> Result<u64, String> {
+    let value: u64 = raw.parse().map_err(|e| format!("invalid window size: {e}"))?;
+    if value == 0 {
+        return Err("window size must be greater than 0".to_string());
+    }
+    Ok(value)
+}
+
+/// A carve discovered during the scan, not yet deduplicated or saved.
+struct Candidate {
+    offset: u64,
+    size: usize,
+    carver_index: usize,
+}
+
+/// Drops candidates fully contained within another candidate's span,
+/// keeping the outermost (largest) file when carves are nested.
+fn resolve_nested(mut candidates: Vec<Candidate>) -> Vec<Candidate> {
+    candidates.sort_by_key(|c| (c.offset, std::cmp::Reverse(c.size)));
+
+    let mut kept: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        let end = candidate.offset + candidate.size as u64;
+        let contained = kept.iter().any(|k| {
+            let kept_end = k.offset + k.size as u64;
+            candidate.offset >= k.offset && end <= kept_end
+        });
+        if !contained {
+            kept.push(candidate);
+        }
+    }
+    kept
 }
 
 fn main() -> Result<()> {
+    let start_time = Instant::now();
     let args = Args::parse();
 
     // Initialize the thread pool if specified
@@ -40,76 +108,188 @@ fn main() -> Result<()> {
 
     fs::create_dir_all(&args.output)?;
 
-    let file = File::open(&args.input)?;
-    let mmap = unsafe { MmapOptions::new().map(&file)? };
+    let block_reader = BlockReader::open(&args.input)?;
+    let total_len = block_reader.len();
 
     // Register supported carvers
-    let carvers: Vec<Arc<dyn Carver>> = vec![
+    let mut carvers: Vec<Arc<dyn Carver>> = vec![
         Arc::new(JpegCarver),
         Arc::new(PngCarver),
         Arc::new(GifCarver),
         Arc::new(PdfCarver),
+        Arc::new(TiffCarver),
     ];
 
+    if let Some(config_path) = &args.config {
+        for carver in load_carvers(config_path)? {
+            carvers.push(Arc::new(carver));
+        }
+    }
+
     println!(
         "rforemost v{} - Starting scan of {} bytes",
         env!("CARGO_PKG_VERSION"),
-        mmap.len()
+        total_len
     );
 
     // Optimization: Identify the first byte of every possible header magic.
     // This allows us to skip bytes that cannot possibly be the start of a header.
     let mut first_bytes = [false; 256];
     for carver in &carvers {
-        first_bytes[carver.header_magic()[0] as usize] = true;
+        for byte in carver.header_first_bytes() {
+            first_bytes[byte as usize] = true;
+        }
     }
     let first_bytes = Arc::new(first_bytes);
 
-    // Use a larger chunk size to reduce Rayon overhead and improve cache locality.
-    let chunk_size = 1024 * 1024; // 1MB chunks
-    let total_len = mmap.len();
-
-        (0..total_len)
-            .into_par_iter()
-            .step_by(chunk_size)
-            .for_each(|chunk_start| {
-                let data = &mmap[..];
-                
-                for offset in chunk_start..std::cmp::min(chunk_start + chunk_size, total_len) {
-                    // Quick check: skip if the current byte doesn't match any known header start.
-                    if !first_bytes[data[offset] as usize] {
-                        continue;
+    // Windows overlap by the largest carver's max span so a header near the
+    // end of one window still has its full extent available to read.
+    let overlap = carvers
+        .iter()
+        .map(|c| c.max_extract_span())
+        .max()
+        .unwrap_or(0);
+    let window_size = args.window_size;
+
+    let window_starts: Vec<u64> = (0..total_len).step_by(window_size as usize).collect();
+
+    let covered = CoveredRanges::new();
+    let candidates: Mutex<Vec<Candidate>> = Mutex::new(Vec::new());
+
+    window_starts.into_par_iter().try_for_each(|window_start| {
+        let core_len = std::cmp::min(window_size, total_len - window_start) as usize;
+        let data = block_reader.read_window(window_start, core_len + overlap)?;
+
+        for local_offset in 0..core_len {
+            if !first_bytes[data[local_offset] as usize] {
+                continue;
+            }
+            let file_offset = window_start + local_offset as u64;
+            if !args.carve_nested && covered.is_covered(file_offset) {
+                continue;
+            }
+
+            for (carver_index, carver) in carvers.iter().enumerate() {
+                if carver.matches_header(&data, local_offset)
+                    && let Some(size) = carver.extract(&data, local_offset)
+                {
+                    if !args.carve_nested {
+                        covered.record(file_offset, file_offset + size as u64);
+                    }
+                    candidates.lock().unwrap().push(Candidate {
+                        offset: file_offset,
+                        size,
+                        carver_index,
+                    });
+                }
+            }
+        }
+
+        Ok::<(), anyhow::Error>(())
+    })?;
+
+    let mut candidates = candidates.into_inner().unwrap();
+    if !args.carve_nested {
+        // Chunks run out of order, so the in-scan skip above is only
+        // best-effort; this pass is the authoritative one.
+        candidates = resolve_nested(candidates);
+    }
+
+    let audit = Mutex::new(AuditReport::new());
+
+    candidates.into_par_iter().try_for_each(|candidate| {
+        let carver = &carvers[candidate.carver_index];
+        let file_data = block_reader.read_window(candidate.offset, candidate.size)?;
+
+        let metadata = args
+            .extract_metadata
+            .then(|| extract_exif(&file_data, 0, carver.extension()))
+            .flatten();
+        let filename = carved_filename(metadata.as_ref(), candidate.offset, carver.extension());
+        let path = args.output.join(&filename);
+
+        match save_file_with_hash(&path, &file_data, args.hash) {
+            Ok(hash) => {
+                audit.lock().unwrap().record(AuditEntry {
+                    offset: candidate.offset,
+                    size: candidate.size,
+                    filename: filename.clone(),
+                    extension: carver.extension().to_string(),
+                    hash,
+                });
+                if let Some(metadata) = &metadata {
+                    let sidecar_path = args.output.join(format!("{filename}.json"));
+                    if let Err(e) = write_sidecar(&sidecar_path, metadata) {
+                        eprintln!("Error writing metadata sidecar for {}: {}", filename, e);
                     }
-    
-                                    for carver in &carvers {
-    
-                                        if carver.matches_header(data, offset)
-    
-                                            && let Some(size) = carver.extract(data, offset)
-    
-                                        {
-    
-                                            let file_data = &data[offset..offset + size];
-    
-                                            let filename = format!("file_{:08}.{}", offset, carver.extension());
-    
-                                            let path = args.output.join(filename);
-    
-                    
-    
-                                            if let Err(e) = save_file(&path, file_data) {
-    
-                                                eprintln!("Error saving file at offset {}: {}", offset, e);
-    
-                                            }
-    
-                                        }
-    
-                                    }
-    
-                    
                 }
-            });
-        println!("Scan complete. Recovered files are in {:?}", args.output);
+            }
+            Err(e) => {
+                audit.lock().unwrap().record_failure();
+                eprintln!("Error saving file at offset {}: {}", candidate.offset, e);
+            }
+        }
+
+        Ok::<(), anyhow::Error>(())
+    })?;
+
+    let mut audit = audit.into_inner().unwrap();
+    audit.stats.bytes_scanned = total_len;
+    audit.stats.elapsed_secs = start_time.elapsed().as_secs_f64();
+
+    audit.write_text(&args.output.join("audit.txt"))?;
+    if args.audit_json {
+        audit.write_json(&args.output.join("audit.json"))?;
+    }
+
+    println!("Scan complete. Recovered files are in {:?}", args.output);
     Ok(())
 }
+
+#[cfg(test)]
+mod resolve_nested_tests {
+    use super::*;
+
+    fn candidate(offset: u64, size: usize) -> Candidate {
+        Candidate {
+            offset,
+            size,
+            carver_index: 0,
+        }
+    }
+
+    fn spans(candidates: &[Candidate]) -> Vec<(u64, usize)> {
+        candidates.iter().map(|c| (c.offset, c.size)).collect()
+    }
+
+    #[test]
+    fn drops_candidate_fully_contained_in_another() {
+        let kept = resolve_nested(vec![candidate(0, 1000), candidate(100, 50)]);
+        assert_eq!(spans(&kept), vec![(0, 1000)]);
+    }
+
+    #[test]
+    fn keeps_disjoint_candidates() {
+        let kept = resolve_nested(vec![candidate(0, 100), candidate(200, 100)]);
+        assert_eq!(spans(&kept), vec![(0, 100), (200, 100)]);
+    }
+
+    #[test]
+    fn keeps_partially_overlapping_candidates() {
+        // Neither fully contains the other, so both survive.
+        let kept = resolve_nested(vec![candidate(0, 100), candidate(50, 100)]);
+        assert_eq!(spans(&kept), vec![(0, 100), (50, 100)]);
+    }
+
+    #[test]
+    fn collapses_exact_duplicates_to_one() {
+        let kept = resolve_nested(vec![candidate(10, 50), candidate(10, 50)]);
+        assert_eq!(spans(&kept), vec![(10, 50)]);
+    }
+
+    #[test]
+    fn keeps_outermost_across_multiple_nesting_levels() {
+        let kept = resolve_nested(vec![candidate(100, 10), candidate(0, 1000), candidate(50, 5)]);
+        assert_eq!(spans(&kept), vec![(0, 1000)]);
+    }
+}